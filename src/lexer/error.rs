@@ -0,0 +1,17 @@
+use crate::lexer::tokens::Position;
+
+/// A machine-readable lexing failure, carrying the position it occurred at
+/// so callers can render a diagnostic without re-scanning the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub position: Position,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexErrorKind {
+    UnexpectedChar(char),
+    UnterminatedString,
+    UnterminatedComment,
+    IntegerOverflow,
+}
@@ -1,122 +1,562 @@
+pub mod error;
 pub mod tokens;
 
+use crate::lexer::error::{LexError, LexErrorKind};
 use crate::lexer::tokens::*;
 use crate::Result;
 
-use std::iter::Peekable;
-use std::vec::IntoIter;
-use regex::Regex;
+use std::str::Chars;
+
+/// Reads `path` into an owned `String` for a [`Lexer`] to borrow.
+///
+/// A `Lexer` borrows its source text rather than owning it, so reading the
+/// file is split out from construction: keep the returned `String` alive
+/// for as long as the `Lexer` you build from it with [`Lexer::from_text`].
+pub fn read_source_file(path: &str) -> std::io::Result<String> {
+    std::fs::read_to_string(path)
+}
 
 /// Lexer (or tokenizer) which creates a list of tokens defined in the [`Token`] enum
 /// by implementing the Iterator trait
 ///
+/// Tokenization is driven by a cursor over a `Chars` iterator: `peek`/`peek_at`
+/// clone the cursor (cheap, since `Chars` is just a pointer/length pair) and
+/// `bump` advances it by one character, so a source file is scanned in a
+/// single linear pass with no intermediate buffers or regex compilation. The
+/// `Lexer` borrows its source text rather than owning it, the same way
+/// rustc's lexer borrows from the compiler's source map.
+///
 /// [`Token`]: ./tokens/enum.Token.html
-pub struct Lexer {
-    raw_data: Peekable<IntoIter<char>>,
-    line_count: u32,
+pub struct Lexer<'a> {
+    chars: Chars<'a>,
+    position: Position,
+    options: LexerOptions,
+}
+
+/// Flags controlling how a [`Lexer`] tokenizes its input, beyond the token
+/// grammar itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LexerOptions {
+    /// Whether keyword matching is case-sensitive; `false` for dialects
+    /// that spell keywords in uppercase, e.g. `IF`/`THEN`.
+    pub case_sensitive_keywords: bool,
+    /// Whether comments and runs of whitespace are yielded as
+    /// `TokenKind::Comment`/`TokenKind::Whitespace` tokens instead of being
+    /// skipped. Useful for formatters and doc tooling that need to
+    /// round-trip source exactly.
+    pub emit_trivia: bool,
 }
 
-impl Iterator for Lexer {
+impl Default for LexerOptions {
+    fn default() -> Self {
+        LexerOptions {
+            case_sensitive_keywords: true,
+            emit_trivia: false,
+        }
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
     type Item = Token;
     fn next(&mut self) -> Option<Self::Item> {
-        let token: Token;
-        let token_kind: Result<TokenKind>;
+        loop {
+            // Whitespace: either skip it in place or, with `emit_trivia`,
+            // yield it as its own token.
+            if matches!(self.peek(), Some(' ')) {
+                let start = self.position;
+                while matches!(self.peek(), Some(' ')) {
+                    self.bump();
+                }
+                if self.options.emit_trivia {
+                    let end = self.position;
+                    return Some(Token::new(Ok(TokenKind::Whitespace), Span { start, end }));
+                }
+                continue;
+            }
 
-        let mut text: String = String::new();
+            self.peek()?;
+            let start = self.position;
+            let token_kind: Result<TokenKind, LexError>;
 
-        loop {
-            match self.raw_data.peek() {
-                Some(c) if *c == ' ' => {
-                    self.raw_data.next();
+            // End Line
+            if matches!(self.peek(), Some('\r') | Some('\n')) {
+                let c = self.bump().unwrap();
+                // A `\r\n` pair is a single line break, not two.
+                if c == '\r' && self.peek() == Some('\n') {
+                    self.bump();
+                }
+                token_kind = Ok(TokenKind::EndLine);
+            }
+            // Integer and Float Literals
+            else if matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+                let mut s = String::new();
+                while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+                    s.push(self.bump().unwrap());
+                }
+
+                // Only treat `.` as a decimal point when it's followed by a
+                // digit, so `3.foo` lexes as `3`, `.`, `foo` rather than a
+                // malformed float.
+                let is_float = self.peek() == Some('.')
+                    && matches!(self.peek_at(1), Some(c) if c.is_ascii_digit());
+
+                if is_float {
+                    s.push(self.bump().unwrap()); // '.'
+                    while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+                        s.push(self.bump().unwrap());
+                    }
+                    self.lex_exponent(&mut s);
+
+                    // `s` only ever contains digits, a single '.', and an
+                    // optional exponent built the same way, so this is
+                    // always valid float syntax.
+                    let f = s.parse::<f64>().expect("scanned float literal is always valid");
+                    token_kind = Ok(TokenKind::Literal(Literal::Float(f)));
+                } else {
+                    token_kind = match s.parse::<i32>() {
+                        Ok(i) => Ok(TokenKind::Literal(Literal::Integer(i))),
+                        Err(_) => Err(LexError {
+                            kind: LexErrorKind::IntegerOverflow,
+                            position: start,
+                        }),
+                    };
+                }
+            }
+            // String Literals
+            else if self.peek() == Some('"') {
+                self.bump(); // opening quote
+                let mut s = String::new();
+                let mut terminated = false;
+                while let Some(c) = self.peek() {
+                    if c == '"' {
+                        self.bump();
+                        terminated = true;
+                        break;
+                    }
+                    s.push(c);
+                    self.bump();
+                }
+                token_kind = if terminated {
+                    Ok(TokenKind::Literal(Literal::Str(s)))
+                } else {
+                    Err(LexError {
+                        kind: LexErrorKind::UnterminatedString,
+                        position: start,
+                    })
+                };
+            }
+            // Line Comments
+            else if self.peek() == Some('/') && self.peek_at(1) == Some('/') {
+                let mut text = String::new();
+                text.push(self.bump().unwrap());
+                text.push(self.bump().unwrap());
+                while !matches!(self.peek(), Some('\r') | Some('\n') | None) {
+                    text.push(self.bump().unwrap());
+                }
+                if self.options.emit_trivia {
+                    token_kind = Ok(TokenKind::Comment(text));
+                } else {
                     continue;
                 }
-                Some(_) => {
-                    break;
+            }
+            // Block Comments (nestable)
+            else if self.peek() == Some('/') && self.peek_at(1) == Some('*') {
+                let (text, terminated) = self.lex_block_comment();
+                if !terminated {
+                    token_kind = Err(LexError {
+                        kind: LexErrorKind::UnterminatedComment,
+                        position: start,
+                    });
+                } else if self.options.emit_trivia {
+                    token_kind = Ok(TokenKind::Comment(text));
+                } else {
+                    continue;
                 }
-                None => return None,
             }
-        }
+            // Symbols: arithmetic, comparison, grouping/punctuation, and the
+            // `<-` assignment arrow, matched with maximal munch so `<=` beats
+            // `<` and `<-` is still distinguished from `<`.
+            else if is_symbol_start(self.peek().unwrap()) {
+                match self.lex_symbol() {
+                    Some(s) => token_kind = Ok(TokenKind::Symbol(s)),
+                    None => {
+                        let c = self.bump().unwrap();
+                        token_kind = Err(LexError {
+                            kind: LexErrorKind::UnexpectedChar(c),
+                            position: start,
+                        });
+                    }
+                }
+            }
+            // Identifiers and Keywords
+            else if matches!(self.peek(), Some(c) if c == '_' || c.is_ascii_alphabetic()) {
+                let mut s = String::new();
+                while matches!(self.peek(), Some(c) if c == '_' || c.is_ascii_alphanumeric()) {
+                    s.push(self.bump().unwrap());
+                }
+                token_kind = match Keyword::from_str(&s, self.options.case_sensitive_keywords) {
+                    Some(keyword) => Ok(TokenKind::Keyword(keyword)),
+                    None => Ok(TokenKind::Identifier(s)),
+                };
+            } else {
+                let c = self.bump().unwrap();
+                token_kind = Err(LexError {
+                    kind: LexErrorKind::UnexpectedChar(c),
+                    position: start,
+                });
+            }
 
-        // TODO: Stop cloning String, allow regex expression matching with &str slice.
-        for c in self.raw_data.clone().collect::<Vec<char>>() {
-            text.push(c);
+            let end = self.position;
+            return Some(Token::new(token_kind, Span { start, end }));
         }
+    }
+}
 
-        // End Line
-        if let Some(t) = Regex::new(r#"^[\r\n]"#).unwrap().find(text.as_str()) {
-            for _ in 0..t.end() {
-                self.raw_data.next();
-            }
-            token_kind = Ok(TokenKind::EndLine);
-            self.line_count += 1;
+impl<'a> Lexer<'a> {
+    pub fn from_text(text: &'a str) -> Self {
+        Self::from_text_with_options(text, LexerOptions::default())
+    }
+
+    /// Like [`Lexer::from_text`], but lets the caller choose whether
+    /// keyword matching is case-sensitive.
+    pub fn from_text_with_keyword_case(text: &'a str, case_sensitive_keywords: bool) -> Self {
+        Self::from_text_with_options(
+            text,
+            LexerOptions {
+                case_sensitive_keywords,
+                ..LexerOptions::default()
+            },
+        )
+    }
+
+    pub fn from_text_with_options(text: &'a str, options: LexerOptions) -> Self {
+        Lexer {
+            chars: text.chars(),
+            position: Position::new(),
+            options,
         }
+    }
 
-        // Integer Literal
-        else if let Some(t) = Regex::new(r#"^\d+"#).unwrap().find(text.as_str()) {
-            for _ in 0..t.end() {
-                self.raw_data.next();
+    fn peek(&self) -> Option<char> {
+        self.chars.clone().next()
+    }
+
+    fn peek_at(&self, n: usize) -> Option<char> {
+        self.chars.clone().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        match c {
+            // The '\n' of a `\r\n` pair is what advances the line, so a
+            // lone '\r' (old Mac-style line endings) only advances the
+            // line when it's *not* immediately followed by '\n' — that
+            // case is left for the paired '\n' bump to handle, keeping a
+            // CRLF pair a single line break.
+            '\r' if self.chars.clone().next() == Some('\n') => {
+                self.position.column += 1;
+            }
+            '\r' | '\n' => {
+                self.position.line += 1;
+                self.position.column = 1;
             }
-            let value = t.as_str().parse::<i32>();
-            token_kind = match value {
-                Ok(i) => Ok(TokenKind::Literal(Literal::Integer(i))),
-                _ => Err(format!("Invalid Integer: {}", t.as_str())),
+            _ => {
+                self.position.column += 1;
             }
         }
+        Some(c)
+    }
 
-        // String Literals
-        else if let Some(t) = Regex::new(r#"^"[^"]*""#).unwrap().find(text.as_str()) {
-            let mut s: String = String::new();
-            for _ in 0..t.end() {
-                s.push(self.raw_data.next().unwrap());
-            }
-            let s = &s[1..s.len() - 1];
-            token_kind = Ok(TokenKind::Literal(Literal::Str(s.to_owned())));
+    /// Appends a `[eE][+-]?\d+` exponent to `s` if one is present, leaving
+    /// the cursor untouched if it isn't (e.g. a bare trailing `e`).
+    fn lex_exponent(&mut self, s: &mut String) {
+        if !matches!(self.peek(), Some('e') | Some('E')) {
+            return;
         }
 
-        // Comments
-        else if let Some(t) = Regex::new(r#"^//.*"#).unwrap().find(text.as_str()) {
-            for _ in 0..t.end() {
-                self.raw_data.next().unwrap();
-            }
-            token_kind = self.next()?.token_kind;
+        let sign_offset = if matches!(self.peek_at(1), Some('+') | Some('-')) {
+            2
+        } else {
+            1
+        };
+        if !matches!(self.peek_at(sign_offset), Some(c) if c.is_ascii_digit()) {
+            return;
         }
 
-        // Symbols
-        else if let Some(t) = Regex::new(r#"^(<-|=)"#).unwrap().find(text.as_str()) {
-            let mut s: String = String::new();
-            for _ in 0..t.end() {
-                s.push(self.raw_data.next().unwrap());
-            }
-            token_kind = Ok(TokenKind::Symbol(s));
+        s.push(self.bump().unwrap()); // 'e'/'E'
+        if matches!(self.peek(), Some('+') | Some('-')) {
+            s.push(self.bump().unwrap());
         }
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+            s.push(self.bump().unwrap());
+        }
+    }
 
-        // Identifiers
-        else if let Some(t) = Regex::new(r#"^[_a-zA-Z][_a-zA-Z0-9]*"#).unwrap().find(text.as_str()) {
-            let mut s: String = String::new();
-            for _ in 0..t.end() {
-                s.push(self.raw_data.next().unwrap());
+    /// Matches the longest valid symbol at the cursor (`<-`, `<=`, `>=`,
+    /// `==`, `!=`, or one of the single-character operators/punctuation),
+    /// or `None` if the character(s) at the cursor don't form one.
+    fn lex_symbol(&mut self) -> Option<String> {
+        const TWO_CHAR: &[&str] = &["<-", "<=", ">=", "==", "!="];
+        if let (Some(a), Some(b)) = (self.peek(), self.peek_at(1)) {
+            let two: String = [a, b].iter().collect();
+            if TWO_CHAR.contains(&two.as_str()) {
+                self.bump();
+                self.bump();
+                return Some(two);
             }
-            token_kind = Ok(TokenKind::Identifier(s));
         }
-        else {
-            token_kind = Err(format!("Unexpected Token: '{}'", self.raw_data.next().unwrap()));
+
+        match self.peek() {
+            Some(c) if ONE_CHAR_SYMBOLS.contains(c) => {
+                self.bump();
+                Some(c.to_string())
+            }
+            _ => None,
         }
+    }
+
+    /// Consumes a `/* ... */` comment starting at the cursor, tracking
+    /// nesting depth in a loop so `/* a /* b */ c */` closes on the final
+    /// `*/` rather than the first. Returns the full comment text, including
+    /// delimiters, and whether a closing `*/` was found: an unterminated
+    /// comment consumes to end of file and reports `false`, the same way
+    /// unterminated string literals do.
+    fn lex_block_comment(&mut self) -> (String, bool) {
+        let mut text = String::new();
+        text.push(self.bump().unwrap()); // '/'
+        text.push(self.bump().unwrap()); // '*'
 
-        println!("{:?}", Some(&token_kind));
-        token = Token::new(token_kind, self.line_count);
-        Some(token)
+        let mut depth = 1u32;
+        while depth > 0 {
+            match (self.peek(), self.peek_at(1)) {
+                (Some('/'), Some('*')) => {
+                    text.push(self.bump().unwrap());
+                    text.push(self.bump().unwrap());
+                    depth += 1;
+                }
+                (Some('*'), Some('/')) => {
+                    text.push(self.bump().unwrap());
+                    text.push(self.bump().unwrap());
+                    depth -= 1;
+                }
+                (Some(_), _) => text.push(self.bump().unwrap()),
+                (None, _) => break,
+            }
+        }
+        (text, depth == 0)
     }
 }
 
-impl Lexer {
-    pub fn from_text(text: &str) -> Self {
-        Lexer {
-            raw_data: text.chars().collect::<Vec<char>>().into_iter().peekable(),
-            line_count: 1u32,
-        }
+/// Arithmetic, grouping, and punctuation symbols that are exactly one
+/// character wide; `<-`, `<=`, `>=`, `==`, and `!=` are handled separately
+/// by [`Lexer::lex_symbol`] since they need a second character of lookahead.
+const ONE_CHAR_SYMBOLS: &str = "+-*/^<>=(){}[],;:.";
+
+fn is_symbol_start(c: char) -> bool {
+    ONE_CHAR_SYMBOLS.contains(c) || c == '!'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<TokenKind> {
+        Lexer::from_text(text)
+            .map(|t| t.token_kind.expect("unexpected lex error"))
+            .collect()
+    }
+
+    #[test]
+    fn keywords_are_classified_separately_from_identifiers() {
+        assert_eq!(
+            kinds("if x then true else false"),
+            vec![
+                TokenKind::Keyword(Keyword::If),
+                TokenKind::Identifier("x".to_owned()),
+                TokenKind::Keyword(Keyword::Then),
+                TokenKind::Keyword(Keyword::True),
+                TokenKind::Keyword(Keyword::Else),
+                TokenKind::Keyword(Keyword::False),
+            ]
+        );
+    }
+
+    #[test]
+    fn keyword_case_sensitivity_is_configurable() {
+        let case_sensitive: Vec<TokenKind> =
+            Lexer::from_text_with_keyword_case("IF", true)
+                .map(|t| t.token_kind.unwrap())
+                .collect();
+        assert_eq!(case_sensitive, vec![TokenKind::Identifier("IF".to_owned())]);
+
+        let case_insensitive: Vec<TokenKind> =
+            Lexer::from_text_with_keyword_case("IF", false)
+                .map(|t| t.token_kind.unwrap())
+                .collect();
+        assert_eq!(case_insensitive, vec![TokenKind::Keyword(Keyword::If)]);
     }
 
-    pub fn from_file(path: &str) -> std::io::Result<Self> {
-        Ok(Self::from_text(&std::fs::read_to_string(path)?))
+    #[test]
+    fn nested_block_comments_close_on_the_final_terminator() {
+        let tokens: Vec<TokenKind> = kinds("1 /* a /* b */ c */ 2");
+        assert_eq!(
+            tokens,
+            vec![
+                TokenKind::Literal(Literal::Integer(1)),
+                TokenKind::Literal(Literal::Integer(2)),
+            ]
+        );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn emit_trivia_yields_comment_and_whitespace_tokens() {
+        let options = LexerOptions {
+            emit_trivia: true,
+            ..LexerOptions::default()
+        };
+        let tokens: Vec<TokenKind> = Lexer::from_text_with_options("1  /* c */2", options)
+            .map(|t| t.token_kind.unwrap())
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                TokenKind::Literal(Literal::Integer(1)),
+                TokenKind::Whitespace,
+                TokenKind::Comment("/* c */".to_owned()),
+                TokenKind::Literal(Literal::Integer(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn crlf_is_a_single_end_line_token() {
+        let tokens: Vec<Token> = Lexer::from_text("a\r\nb").collect();
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[1].token_kind, Ok(TokenKind::EndLine));
+        assert_eq!(tokens[2].line(), 2);
+    }
+
+    #[test]
+    fn lone_cr_and_lone_lf_each_advance_one_line() {
+        assert_eq!(kinds("a\rb"), vec![
+            TokenKind::Identifier("a".to_owned()),
+            TokenKind::EndLine,
+            TokenKind::Identifier("b".to_owned()),
+        ]);
+        let tokens: Vec<Token> = Lexer::from_text("a\rb").collect();
+        assert_eq!(tokens[2].line(), 2);
+        let tokens: Vec<Token> = Lexer::from_text("a\nb").collect();
+        assert_eq!(tokens[2].line(), 2);
+    }
+
+    #[test]
+    fn float_literals_with_and_without_exponent() {
+        assert_eq!(
+            kinds("3.14 2.5e10 1.0E-3 6.0e+2"),
+            vec![
+                TokenKind::Literal(Literal::Float(3.14)),
+                TokenKind::Literal(Literal::Float(2.5e10)),
+                TokenKind::Literal(Literal::Float(1.0E-3)),
+                TokenKind::Literal(Literal::Float(6.0e2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn dot_not_followed_by_a_digit_is_not_part_of_a_float() {
+        assert_eq!(
+            kinds("3.foo"),
+            vec![
+                TokenKind::Literal(Literal::Integer(3)),
+                TokenKind::Symbol(".".to_owned()),
+                TokenKind::Identifier("foo".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn symbols_are_matched_with_maximal_munch() {
+        assert_eq!(
+            kinds("<- <= < >= == != = ( ) { } [ ] , ; : + - * / ^"),
+            vec![
+                TokenKind::Symbol("<-".to_owned()),
+                TokenKind::Symbol("<=".to_owned()),
+                TokenKind::Symbol("<".to_owned()),
+                TokenKind::Symbol(">=".to_owned()),
+                TokenKind::Symbol("==".to_owned()),
+                TokenKind::Symbol("!=".to_owned()),
+                TokenKind::Symbol("=".to_owned()),
+                TokenKind::Symbol("(".to_owned()),
+                TokenKind::Symbol(")".to_owned()),
+                TokenKind::Symbol("{".to_owned()),
+                TokenKind::Symbol("}".to_owned()),
+                TokenKind::Symbol("[".to_owned()),
+                TokenKind::Symbol("]".to_owned()),
+                TokenKind::Symbol(",".to_owned()),
+                TokenKind::Symbol(";".to_owned()),
+                TokenKind::Symbol(":".to_owned()),
+                TokenKind::Symbol("+".to_owned()),
+                TokenKind::Symbol("-".to_owned()),
+                TokenKind::Symbol("*".to_owned()),
+                TokenKind::Symbol("/".to_owned()),
+                TokenKind::Symbol("^".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn bare_bang_is_an_unexpected_char() {
+        let tokens: Vec<Token> = Lexer::from_text("!").collect();
+        assert_eq!(
+            tokens[0].token_kind,
+            Err(LexError {
+                kind: LexErrorKind::UnexpectedChar('!'),
+                position: Position { line: 1, column: 1 },
+            })
+        );
+    }
+
+    #[test]
+    fn terminated_string_literal_yields_its_contents() {
+        assert_eq!(
+            kinds("\"hello\""),
+            vec![TokenKind::Literal(Literal::Str("hello".to_owned()))]
+        );
+    }
+
+    #[test]
+    fn unterminated_string_literal_errors_at_the_opening_quote() {
+        let tokens: Vec<Token> = Lexer::from_text("\"abc").collect();
+        assert_eq!(
+            tokens[0].token_kind,
+            Err(LexError {
+                kind: LexErrorKind::UnterminatedString,
+                position: Position { line: 1, column: 1 },
+            })
+        );
+    }
+
+    #[test]
+    fn integer_literal_overflowing_i32_errors() {
+        let tokens: Vec<Token> = Lexer::from_text("99999999999").collect();
+        assert_eq!(
+            tokens[0].token_kind,
+            Err(LexError {
+                kind: LexErrorKind::IntegerOverflow,
+                position: Position { line: 1, column: 1 },
+            })
+        );
+    }
+
+    #[test]
+    fn unterminated_block_comment_errors_at_its_opening_delimiter() {
+        let tokens: Vec<Token> = Lexer::from_text("/* a /* b").collect();
+        assert_eq!(
+            tokens[0].token_kind,
+            Err(LexError {
+                kind: LexErrorKind::UnterminatedComment,
+                position: Position { line: 1, column: 1 },
+            })
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::lexer::error::LexError;
+use crate::Result;
+
+/// A 1-indexed line/column location within the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Position {
+    pub fn new() -> Self {
+        Position { line: 1, column: 1 }
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position::new()
+    }
+}
+
+/// The range of source text a [`Token`] was lexed from, from `start`
+/// (inclusive) to `end` (exclusive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A single lexical token produced by the [`Lexer`].
+///
+/// [`Lexer`]: ../struct.Lexer.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub token_kind: Result<TokenKind, LexError>,
+    pub span: Span,
+}
+
+impl Token {
+    pub fn new(token_kind: Result<TokenKind, LexError>, span: Span) -> Self {
+        Token { token_kind, span }
+    }
+
+    /// The line the token starts on, kept around for callers that don't
+    /// need the full [`Span`].
+    pub fn line(&self) -> u32 {
+        self.span.start.line
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    EndLine,
+    Literal(Literal),
+    Symbol(String),
+    Identifier(String),
+    Keyword(Keyword),
+    /// A `//` or `/* */` comment, text included verbatim. Only produced
+    /// when the lexer is constructed with [`LexerOptions::emit_trivia`];
+    /// otherwise comments are skipped silently.
+    ///
+    /// [`LexerOptions::emit_trivia`]: super::LexerOptions::emit_trivia
+    Comment(String),
+    /// A run of consecutive space characters. Only produced when the lexer
+    /// is constructed with `emit_trivia` set.
+    Whitespace,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Integer(i32),
+    Float(f64),
+    Str(String),
+}
+
+/// A reserved word. Matched against identifier text by the lexer via
+/// [`Keyword::from_str`], respecting the dialect's configured
+/// case-sensitivity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keyword {
+    If,
+    Then,
+    Else,
+    While,
+    For,
+    Return,
+    True,
+    False,
+}
+
+impl Keyword {
+    /// Looks `s` up in the keyword table, comparing case-sensitively unless
+    /// `case_sensitive` is `false` (for pseudocode dialects that spell
+    /// keywords in uppercase, e.g. `IF`/`THEN`).
+    pub fn from_str(s: &str, case_sensitive: bool) -> Option<Self> {
+        let lowered;
+        let key = if case_sensitive {
+            s
+        } else {
+            lowered = s.to_ascii_lowercase();
+            lowered.as_str()
+        };
+
+        match key {
+            "if" => Some(Keyword::If),
+            "then" => Some(Keyword::Then),
+            "else" => Some(Keyword::Else),
+            "while" => Some(Keyword::While),
+            "for" => Some(Keyword::For),
+            "return" => Some(Keyword::Return),
+            "true" => Some(Keyword::True),
+            "false" => Some(Keyword::False),
+            _ => None,
+        }
+    }
+}
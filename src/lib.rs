@@ -0,0 +1,9 @@
+pub mod lexer;
+
+/// Shared result alias used across the compiler's front end. Defaults the
+/// error type to `String` for stages that don't yet have a dedicated error
+/// type, while letting others (e.g. the lexer's [`LexError`]) plug in their
+/// own.
+///
+/// [`LexError`]: lexer/error/struct.LexError.html
+pub type Result<T, E = String> = std::result::Result<T, E>;